@@ -0,0 +1,103 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use cg::{self, WhereClause};
+use quote::{ToTokens, Tokens};
+use syn;
+use synstructure::{Structure, VariantInfo};
+
+use to_css::{self, CssFieldAttrs, CssInputAttrs, CssVariantAttrs};
+
+pub fn derive(input: syn::DeriveInput) -> Tokens {
+    let name = &input.ident;
+    let trait_path = parse_quote!(::style_traits::SpecifiedValueInfo);
+    let (impl_generics, ty_generics, mut where_clause) =
+        cg::trait_parts(&input, &trait_path);
+
+    let input_attrs = cg::parse_input_attrs::<CssInputAttrs>(&input);
+    let rename_all = input_attrs.rename_all.clone();
+
+    let mut stmts = Tokens::new();
+    if let Some(function) = input_attrs.function {
+        // The whole value is a function; we contribute the function name itself, but
+        // don't recurse into the fields' value-types.
+        let keyword = function.explicit().unwrap_or_else(|| {
+            to_css::to_css_identifier(name.as_ref(), rename_all.as_ref().map(String::as_str))
+        });
+        stmts.append_all(quote! {
+            f(&[#keyword]);
+        });
+    } else {
+        let s = Structure::new(&input);
+        for variant in s.variants() {
+            derive_variant(
+                variant,
+                &mut where_clause,
+                &mut stmts,
+                rename_all.as_ref().map(String::as_str),
+            );
+        }
+    }
+
+    quote! {
+        impl #impl_generics ::style_traits::SpecifiedValueInfo for #name #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn collect_completion_keywords(f: &mut FnMut(&[&'static str])) {
+                #stmts
+            }
+        }
+    }
+}
+
+fn derive_variant(
+    variant: &VariantInfo,
+    where_clause: &mut WhereClause,
+    stmts: &mut Tokens,
+    default_rename_rule: Option<&str>,
+) {
+    let ast = variant.ast();
+    let variant_attrs = cg::parse_variant_attrs::<CssVariantAttrs>(&ast);
+    if variant_attrs.skip {
+        return;
+    }
+
+    let bindings = variant.bindings();
+    let rename_rule = variant_attrs.rename_all.as_ref().map(String::as_str).or(default_rename_rule);
+    let identifier = to_css::to_css_identifier(ast.ident.as_ref(), rename_rule);
+
+    // Mirrors `to_css.rs`'s `derive_variant_arm`: a plain keyword, the variant's own
+    // fields, or its bare identifier, with `dimension`/`function` layered on top.
+    if let Some(keyword) = variant_attrs.keyword {
+        stmts.append_all(quote! {
+            f(&[#keyword]);
+        });
+    } else if !bindings.is_empty() {
+        for binding in bindings {
+            let field_attrs = cg::parse_field_attrs::<CssFieldAttrs>(&binding.ast());
+            if field_attrs.skip {
+                continue;
+            }
+            let ty = &binding.ast().ty;
+            where_clause.add_trait_bound(ty);
+            stmts.append_all(quote! {
+                <#ty as ::style_traits::SpecifiedValueInfo>::collect_completion_keywords(f);
+            });
+        }
+    } else {
+        stmts.append_all(quote! {
+            f(&[#identifier]);
+        });
+    }
+
+    if variant_attrs.dimension {
+        stmts.append_all(quote! {
+            f(&[#identifier]);
+        });
+    } else if let Some(function) = variant_attrs.function {
+        let keyword = function.explicit().unwrap_or(identifier);
+        stmts.append_all(quote! {
+            f(&[#keyword]);
+        });
+    }
+}
@@ -8,21 +8,120 @@ use quote::{ToTokens, Tokens};
 use syn::{self, Data};
 use synstructure::{BindingInfo, Structure, VariantInfo};
 
+/// Converts a Rust identifier to a CSS identifier per `rename_rule` (one of `"kebab-case"`,
+/// `"lowercase"`, `"UPPERCASE"`, `"snake_case"` or `"camelCase"`), falling back to
+/// `cg::to_css_identifier`'s default kebab-case conversion when `rename_rule` is `None`.
+///
+/// Like that default conversion, a leading `Moz`/`Webkit` vendor prefix is special-cased
+/// so e.g. `MozBorderRadius` still renders as `-moz-border-radius` under `"kebab-case"`
+/// rather than losing its leading `-`.
+pub(crate) fn to_css_identifier(ident: &str, rename_rule: Option<&str>) -> String {
+    let rename_rule = match rename_rule {
+        Some(rule) => rule,
+        None => return cg::to_css_identifier(ident),
+    };
+
+    let (vendor_prefix, rest) = strip_vendor_prefix(ident);
+    let mut words = Vec::new();
+    if let Some(prefix) = vendor_prefix {
+        words.push(prefix.to_string());
+    }
+    words.extend(split_ident_words(rest));
+
+    let cased = match rename_rule {
+        "kebab-case" => words.join("-"),
+        "lowercase" => words.concat(),
+        "UPPERCASE" => words.concat().to_uppercase(),
+        "snake_case" => words.join("_"),
+        "camelCase" => camel_case_join(&words),
+        other => panic!("unknown #[css(rename_all = \"{}\")] rule", other),
+    };
+
+    match (vendor_prefix, rename_rule) {
+        (Some(_), "kebab-case") => format!("-{}", cased),
+        (Some(_), "snake_case") => format!("_{}", cased),
+        _ => cased,
+    }
+}
+
+fn camel_case_join(words: &[String]) -> String {
+    let mut result = String::new();
+    for (i, word) in words.iter().enumerate() {
+        if i == 0 {
+            result.push_str(word);
+        } else {
+            let mut chars = word.chars();
+            if let Some(first) = chars.next() {
+                result.extend(first.to_uppercase());
+                result.push_str(chars.as_str());
+            }
+        }
+    }
+    result
+}
+
+/// Strips a leading `Moz`/`Webkit` vendor prefix off a Rust identifier, the way
+/// `cg::to_css_identifier` does, returning the lowercased prefix word (if any) and the
+/// remainder of the identifier.
+fn strip_vendor_prefix(ident: &str) -> (Option<&'static str>, &str) {
+    for &(prefix, lower) in &[("Moz", "moz"), ("Webkit", "webkit")] {
+        if ident.starts_with(prefix) && ident[prefix.len()..].starts_with(char::is_uppercase) {
+            return (Some(lower), &ident[prefix.len()..]);
+        }
+    }
+    (None, ident)
+}
+
+/// Splits a Rust identifier into lowercase words, on `_` and lowercase-to-uppercase case
+/// boundaries, the way serde_derive's internal case module does for its own `rename_all`.
+/// A run of consecutive uppercase letters (as in a `SCREAMING_SNAKE_CASE` `bitflags!`
+/// constant, or an acronym like `HTTP`) stays a single word.
+fn split_ident_words(ident: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut prev_lower = false;
+    for c in ident.chars() {
+        if c == '_' {
+            if !current.is_empty() {
+                words.push(current.to_lowercase());
+                current = String::new();
+            }
+            prev_lower = false;
+            continue;
+        }
+        if c.is_uppercase() && prev_lower {
+            words.push(current.to_lowercase());
+            current = String::new();
+        }
+        prev_lower = c.is_lowercase();
+        current.push(c);
+    }
+    if !current.is_empty() {
+        words.push(current.to_lowercase());
+    }
+    words
+}
+
 pub fn derive(input: syn::DeriveInput) -> Tokens {
+    let input_attrs = cg::parse_input_attrs::<CssInputAttrs>(&input);
+    if let Some(ref bitflags) = input_attrs.bitflags {
+        return derive_bitflags(&input, bitflags);
+    }
+
     let name = &input.ident;
     let trait_path = parse_quote!(::style_traits::ToCss);
     let (impl_generics, ty_generics, mut where_clause) =
         cg::trait_parts(&input, &trait_path);
 
-    let input_attrs = cg::parse_input_attrs::<CssInputAttrs>(&input);
     if let Data::Enum(_) = input.data {
         assert!(input_attrs.function.is_none(), "#[css(function)] is not allowed on enums");
         assert!(!input_attrs.comma, "#[css(comma)] is not allowed on enums");
     }
     let s = Structure::new(&input);
 
+    let rename_all = input_attrs.rename_all.clone();
     let match_body = s.each_variant(|variant| {
-        derive_variant_arm(variant, &mut where_clause)
+        derive_variant_arm(variant, &mut where_clause, rename_all.as_ref().map(String::as_str))
     });
 
     let mut impls = quote! {
@@ -59,14 +158,105 @@ pub fn derive(input: syn::DeriveInput) -> Tokens {
     impls
 }
 
+/// Derives `ToCss` for a type generated by the `bitflags!` macro, configured via
+/// `#[css(bitflags(single = "...", mixed = "...", overlapping_bits))]`.
+///
+/// `single` lists the single-bit flags, in the order they should be tried, and `mixed`
+/// lists shorthand flags that stand for a combination of bits. Mixed flags are tested
+/// before the single flags they subsume. With `overlapping_bits`, each written flag's bits
+/// are removed before the next flag is tested, so e.g. a shorthand like `all` wins over its
+/// individual components; without it, those components are written after the shorthand too.
+///
+/// Each entry is a `bitflags!` constant name, e.g. `LINE_THROUGH`, or, when the CSS
+/// keyword isn't just that name's hyphen-separated lowercasing (`line-through`),
+/// `CONST_NAME:keyword`, e.g. `STRIKE:line-through`.
+fn derive_bitflags(input: &syn::DeriveInput, attrs: &CssBitflagAttrs) -> Tokens {
+    let name = &input.ident;
+    let trait_path = parse_quote!(::style_traits::ToCss);
+    let (impl_generics, ty_generics, where_clause) = cg::trait_parts(input, &trait_path);
+
+    let single_flags: Vec<&str> = attrs.single.split_whitespace().collect();
+    let mixed_flags: Vec<&str> = attrs.mixed.split_whitespace().collect();
+
+    let flag_path_and_keyword = |entry: &str| -> (syn::Path, String) {
+        let (flag, keyword) = parse_bitflag_entry(entry);
+        let path = syn::parse_str(&format!("Self::{}", flag))
+            .unwrap_or_else(|e| panic!("invalid bitflags flag {:?}: {}", flag, e));
+        (path, keyword)
+    };
+
+    let single_flag_fast_paths = single_flags.iter().map(|flag| {
+        let (path, keyword) = flag_path_and_keyword(flag);
+        quote! {
+            if *self == #path {
+                return ::std::fmt::Write::write_str(dest, #keyword);
+            }
+        }
+    });
+
+    let subtract_overlapping_bits = attrs.overlapping_bits;
+    let flag_write_arms = mixed_flags.iter().chain(single_flags.iter()).map(|flag| {
+        let (path, keyword) = flag_path_and_keyword(flag);
+        let maybe_subtract = if subtract_overlapping_bits {
+            quote! { bits.remove(#path); }
+        } else {
+            quote! {}
+        };
+        quote! {
+            if bits.contains(#path) {
+                if has_any {
+                    ::std::fmt::Write::write_str(dest, " ")?;
+                }
+                has_any = true;
+                ::std::fmt::Write::write_str(dest, #keyword)?;
+                #maybe_subtract
+            }
+        }
+    });
+
+    quote! {
+        impl #impl_generics ::style_traits::ToCss for #name #ty_generics #where_clause {
+            #[allow(unused_mut)]
+            fn to_css<W>(
+                &self,
+                dest: &mut ::style_traits::CssWriter<W>,
+            ) -> ::std::fmt::Result
+            where
+                W: ::std::fmt::Write,
+            {
+                #(#single_flag_fast_paths)*
+
+                let mut has_any = false;
+                let mut bits = *self;
+                #(#flag_write_arms)*
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Parses one whitespace-separated entry of a `#[css(bitflags(single = "...", mixed = "..."))]`
+/// list into the `bitflags!` constant name and its CSS keyword. An entry of just `NAME` derives
+/// the keyword from `NAME` by lowercasing its `_`-separated words and joining them with `-`
+/// (matching the `SCREAMING_SNAKE_CASE` convention `bitflags!` constants use); `NAME:keyword`
+/// overrides that when the CSS spelling doesn't match.
+fn parse_bitflag_entry(entry: &str) -> (&str, String) {
+    match entry.find(':') {
+        Some(pos) => (&entry[..pos], entry[pos + 1..].to_string()),
+        None => (entry, split_ident_words(entry).join("-")),
+    }
+}
+
 fn derive_variant_arm(
     variant: &VariantInfo,
     where_clause: &mut WhereClause,
+    default_rename_rule: Option<&str>,
 ) -> Tokens {
     let bindings = variant.bindings();
-    let identifier = cg::to_css_identifier(variant.ast().ident.as_ref());
     let ast = variant.ast();
     let variant_attrs = cg::parse_variant_attrs::<CssVariantAttrs>(&ast);
+    let rename_rule = variant_attrs.rename_all.as_ref().map(String::as_str).or(default_rename_rule);
+    let identifier = to_css_identifier(ast.ident.as_ref(), rename_rule);
     let separator = if variant_attrs.comma { ", " } else { " " };
 
     if variant_attrs.dimension {
@@ -125,7 +315,7 @@ fn derive_variant_fields_expr(
         Some(pair) => pair,
         None => return quote! { Ok(()) },
     };
-    if !attrs.iterable && iter.peek().is_none() {
+    if !attrs.iterable && attrs.skip_if.is_none() && iter.peek().is_none() {
         if !attrs.ignore_bound {
             where_clause.add_trait_bound(&first.ast().ty);
         }
@@ -149,9 +339,10 @@ fn derive_single_field_expr(
     attrs: CssFieldAttrs,
     where_clause: &mut WhereClause,
 ) -> Tokens {
-    if attrs.iterable {
+    let skip_if = attrs.skip_if;
+    let write_item = if attrs.iterable {
         if let Some(if_empty) = attrs.if_empty {
-            return quote! {
+            quote! {
                 {
                     let mut iter = #field.iter().peekable();
                     if iter.peek().is_none() {
@@ -162,11 +353,12 @@ fn derive_single_field_expr(
                         }
                     }
                 }
-            };
-        }
-        quote! {
-            for item in #field.iter() {
-                writer.item(&item)?;
+            }
+        } else {
+            quote! {
+                for item in #field.iter() {
+                    writer.item(&item)?;
+                }
             }
         }
     } else {
@@ -174,17 +366,39 @@ fn derive_single_field_expr(
             where_clause.add_trait_bound(&field.ast().ty);
         }
         quote! { writer.item(#field)?; }
+    };
+
+    match skip_if {
+        Some(path) => quote! {
+            if !#path(#field) {
+                #write_item
+            }
+        },
+        None => write_item,
     }
 }
 
 #[darling(attributes(css), default)]
 #[derive(Default, FromDeriveInput)]
-struct CssInputAttrs {
-    derive_debug: bool,
+pub(crate) struct CssInputAttrs {
+    pub(crate) derive_debug: bool,
     // Here because structs variants are also their whole type definition.
-    function: Option<Override<String>>,
+    pub(crate) function: Option<Override<String>>,
     // Here because structs variants are also their whole type definition.
-    comma: bool,
+    pub(crate) comma: bool,
+    bitflags: Option<CssBitflagAttrs>,
+    // Overrides the default kebab-case identifier for every variant/keyword of the type.
+    pub(crate) rename_all: Option<String>,
+}
+
+/// Configuration for deriving `ToCss` on a `bitflags!`-generated type, via
+/// `#[css(bitflags(single = "...", mixed = "...", overlapping_bits))]`.
+#[darling(default)]
+#[derive(Default, FromMeta)]
+struct CssBitflagAttrs {
+    single: String,
+    mixed: String,
+    overlapping_bits: bool,
 }
 
 #[darling(attributes(css), default)]
@@ -195,13 +409,57 @@ pub struct CssVariantAttrs {
     pub dimension: bool,
     pub keyword: Option<String>,
     pub aliases: Option<String>,
+    // Only honored by the `SpecifiedValueInfo` derive; `to_css` doesn't skip variants.
+    pub skip: bool,
+    // Overrides the input-level `rename_all`, if any, for this variant only.
+    pub rename_all: Option<String>,
 }
 
 #[darling(attributes(css), default)]
 #[derive(Default, FromField)]
-struct CssFieldAttrs {
+pub(crate) struct CssFieldAttrs {
     if_empty: Option<String>,
     ignore_bound: bool,
     iterable: bool,
-    skip: bool,
+    pub(crate) skip: bool,
+    // A path to a `fn(&T) -> bool`; the field is omitted from serialization at runtime
+    // when it returns true. The CSS analogue of serde's `skip_serializing_if`.
+    skip_if: Option<syn::Path>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_bitflag_entry, split_ident_words, to_css_identifier};
+
+    #[test]
+    fn bitflag_entry_derives_keyword_from_screaming_snake_case() {
+        let (flag, keyword) = parse_bitflag_entry("LINE_THROUGH");
+        assert_eq!(flag, "LINE_THROUGH");
+        assert_eq!(keyword, "line-through");
+    }
+
+    #[test]
+    fn bitflag_entry_honors_explicit_keyword_override() {
+        let (flag, keyword) = parse_bitflag_entry("ALL:all");
+        assert_eq!(flag, "ALL");
+        assert_eq!(keyword, "all");
+    }
+
+    #[test]
+    fn split_ident_words_keeps_screaming_snake_case_runs_together() {
+        assert_eq!(split_ident_words("LINE_THROUGH"), vec!["line", "through"]);
+    }
+
+    #[test]
+    fn split_ident_words_still_splits_pascal_case() {
+        assert_eq!(split_ident_words("MozBorderRadius"), vec!["moz", "border", "radius"]);
+    }
+
+    #[test]
+    fn rename_all_snake_case_keeps_moz_prefix_underscore() {
+        assert_eq!(
+            to_css_identifier("MozBorderRadius", Some("snake_case")),
+            "_moz_border_radius"
+        );
+    }
 }